@@ -0,0 +1,43 @@
+use chroma_test::benchmark::tokio_multi_thread;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+
+const BLOCK_SIZES: [usize; 3] = [4096, 65536, 1048576];
+
+fn random_block_bytes(size: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..size).map(|_| rng.gen()).collect()
+}
+
+// Mirrors `blockstore::arrow::block_manager::compress`'s codec dispatch so this bench can compare
+// codecs without depending on the worker crate's private internals.
+fn encode(codec: &str, bytes: &[u8]) -> Vec<u8> {
+    match codec {
+        "none" => bytes.to_vec(),
+        "lz4" => lz4_flex::compress_prepend_size(bytes),
+        "zstd" => zstd::encode_all(bytes, 3).expect("zstd compression over an in-memory buffer cannot fail"),
+        _ => unreachable!("unknown codec {codec}"),
+    }
+}
+
+fn bench_encode_block(criterion: &mut Criterion) {
+    let _runtime = tokio_multi_thread();
+
+    let mut group = criterion.benchmark_group("encode-block");
+    for block_size in BLOCK_SIZES {
+        let bytes = random_block_bytes(block_size);
+        for codec in ["none", "lz4", "zstd"] {
+            group.bench_with_input(
+                BenchmarkId::new(codec, block_size),
+                &bytes,
+                |bencher, bytes| {
+                    bencher.iter(|| encode(codec, bytes));
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode_block);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use chroma_error::{ChromaError, ErrorCodes};
+use thiserror::Error;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Integrity errors raised while loading a block from the BlockManager.
+#[derive(Error, Debug)]
+pub(crate) enum BlockChecksumError {
+    #[error("Block checksum mismatch: expected {expected:x}, computed {computed:x}")]
+    BlockChecksumMismatch { expected: u64, computed: u64 },
+}
+
+impl ChromaError for BlockChecksumError {
+    fn code(&self) -> ErrorCodes {
+        match self {
+            BlockChecksumError::BlockChecksumMismatch { .. } => ErrorCodes::DataLoss,
+        }
+    }
+}
+
+/// Computes the xxh3 checksum stored in a block's header at write time.
+pub(crate) fn checksum_block_bytes(bytes: &[u8]) -> u64 {
+    xxh3_64(bytes)
+}
+
+/// Verifies `bytes` against the checksum recorded in its block header when it was written.
+pub(crate) fn verify_block_checksum(
+    bytes: &[u8],
+    expected: u64,
+) -> Result<(), BlockChecksumError> {
+    let computed = checksum_block_bytes(bytes);
+    if computed == expected {
+        Ok(())
+    } else {
+        Err(BlockChecksumError::BlockChecksumMismatch { expected, computed })
+    }
+}
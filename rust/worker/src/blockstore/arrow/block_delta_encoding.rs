@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use xxhash_rust::xxh3::xxh3_64;
+
+// The rsync-style matching window. Shorter windows catch smaller edits at the cost of a larger
+// signature; this is a reasonable middle ground for the record- and metadata-sized blocks this
+// blockstore writes.
+const WINDOW_SIZE: usize = 64;
+const ROLLING_BASE: u64 = 1_000_000_007;
+
+// A weak checksum over a fixed-size window that can be updated in O(1) per byte as the window
+// slides, rather than rehashing the whole window on every shift.
+struct RollingHash {
+    hash: u64,
+    // ROLLING_BASE^(WINDOW_SIZE - 1), used to remove the outgoing byte's contribution.
+    high_order_term: u64,
+}
+
+impl RollingHash {
+    fn new(window: &[u8]) -> Self {
+        let hash = window
+            .iter()
+            .fold(0u64, |acc, &b| acc.wrapping_mul(ROLLING_BASE).wrapping_add(b as u64));
+        let high_order_term = (0..window.len().saturating_sub(1))
+            .fold(1u64, |acc, _| acc.wrapping_mul(ROLLING_BASE));
+        Self {
+            hash,
+            high_order_term,
+        }
+    }
+
+    fn roll(&mut self, outgoing: u8, incoming: u8) {
+        self.hash = self
+            .hash
+            .wrapping_sub((outgoing as u64).wrapping_mul(self.high_order_term));
+        self.hash = self.hash.wrapping_mul(ROLLING_BASE).wrapping_add(incoming as u64);
+    }
+}
+
+/// A signature of a parent block: every `WINDOW_SIZE`-byte window, keyed by its weak rolling
+/// hash, mapping to the offsets it occurs at plus the strong (xxh3) hash used to confirm a weak
+/// hash collision is a real match before emitting a copy segment.
+pub(crate) struct BlockSignature<'parent> {
+    parent: &'parent [u8],
+    windows: HashMap<u64, Vec<(usize, u64)>>,
+}
+
+impl<'parent> BlockSignature<'parent> {
+    pub(crate) fn build(parent: &'parent [u8]) -> Self {
+        let mut windows: HashMap<u64, Vec<(usize, u64)>> = HashMap::new();
+        if parent.len() >= WINDOW_SIZE {
+            let mut rolling = RollingHash::new(&parent[0..WINDOW_SIZE]);
+            for offset in 0..=(parent.len() - WINDOW_SIZE) {
+                if offset > 0 {
+                    rolling.roll(parent[offset - 1], parent[offset + WINDOW_SIZE - 1]);
+                }
+                let strong = xxh3_64(&parent[offset..offset + WINDOW_SIZE]);
+                windows.entry(rolling.hash).or_default().push((offset, strong));
+            }
+        }
+        Self { parent, windows }
+    }
+
+    fn find_match(&self, weak: u64, window: &[u8]) -> Option<usize> {
+        let strong = xxh3_64(window);
+        self.windows.get(&weak)?.iter().find_map(|&(offset, candidate_strong)| {
+            (candidate_strong == strong && &self.parent[offset..offset + WINDOW_SIZE] == window)
+                .then_some(offset)
+        })
+    }
+}
+
+/// One segment of a delta-encoded block: either bytes copied verbatim from the parent block, or
+/// literal bytes stored inline because no matching parent window was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DeltaSegment {
+    Copy { parent_offset: usize, len: usize },
+    Literal(Vec<u8>),
+}
+
+/// Diffs `new_block` against `signature`'s parent, producing copy/literal segments that
+/// `reconstruct_block` can replay against the same parent bytes to recover `new_block` exactly.
+pub(crate) fn diff_block(new_block: &[u8], signature: &BlockSignature) -> Vec<DeltaSegment> {
+    let mut segments = Vec::new();
+    let mut literal_run = Vec::new();
+    let mut pos = 0;
+    while pos < new_block.len() {
+        let matched = if pos + WINDOW_SIZE <= new_block.len() {
+            let window = &new_block[pos..pos + WINDOW_SIZE];
+            let weak = RollingHash::new(window).hash;
+            signature.find_match(weak, window)
+        } else {
+            None
+        };
+        match matched {
+            Some(parent_offset) => {
+                if !literal_run.is_empty() {
+                    segments.push(DeltaSegment::Literal(std::mem::take(&mut literal_run)));
+                }
+                // Extend the match past the window so adjacent unchanged bytes collapse into one
+                // copy segment instead of one per window.
+                let mut len = WINDOW_SIZE;
+                while pos + len < new_block.len()
+                    && parent_offset + len < signature.parent.len()
+                    && new_block[pos + len] == signature.parent[parent_offset + len]
+                {
+                    len += 1;
+                }
+                segments.push(DeltaSegment::Copy { parent_offset, len });
+                pos += len;
+            }
+            None => {
+                literal_run.push(new_block[pos]);
+                pos += 1;
+            }
+        }
+    }
+    if !literal_run.is_empty() {
+        segments.push(DeltaSegment::Literal(literal_run));
+    }
+    segments
+}
+
+/// Reconstructs a block's bytes by replaying `segments` against `parent`.
+pub(crate) fn reconstruct_block(parent: &[u8], segments: &[DeltaSegment]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(parent.len());
+    for segment in segments {
+        match segment {
+            DeltaSegment::Copy { parent_offset, len } => {
+                out.extend_from_slice(&parent[*parent_offset..*parent_offset + *len]);
+            }
+            DeltaSegment::Literal(bytes) => out.extend_from_slice(bytes),
+        }
+    }
+    out
+}
+
+/// The on-disk size of `segments`, used to decide whether a delta is worth keeping relative to a
+/// full block rewrite.
+pub(crate) fn encoded_len(segments: &[DeltaSegment]) -> usize {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            // A copy segment is two `usize`s; a literal segment is its raw bytes.
+            DeltaSegment::Copy { .. } => 2 * std::mem::size_of::<usize>(),
+            DeltaSegment::Literal(bytes) => bytes.len(),
+        })
+        .sum()
+}
@@ -0,0 +1,456 @@
+use super::block_checksum::{checksum_block_bytes, verify_block_checksum, BlockChecksumError};
+use super::block_delta_encoding::{diff_block, encoded_len, reconstruct_block, BlockSignature, DeltaSegment};
+use super::config::{ArrowBlockfileProviderConfig, CompressionType};
+use super::merkle::{open_segment, segment_root, MerkleTree, SegmentIntegrityError};
+
+/// Compresses a block's bytes on flush using the codec from `ArrowBlockfileProviderConfig`. The
+/// codec is recorded alongside the compressed bytes (see the block header) so a reader can
+/// decode blocks written under a previous provider config.
+pub(crate) fn compress(codec: CompressionType, bytes: &[u8]) -> Vec<u8> {
+    match codec {
+        CompressionType::None => bytes.to_vec(),
+        CompressionType::Lz4 => lz4_flex::compress_prepend_size(bytes),
+        CompressionType::Zstd { level } => {
+            zstd::encode_all(bytes, level).expect("zstd compression over an in-memory buffer cannot fail")
+        }
+    }
+}
+
+/// Reverses `compress` using the codec recorded in the block's header at write time.
+pub(crate) fn decompress(codec: CompressionType, bytes: &[u8]) -> Vec<u8> {
+    match codec {
+        CompressionType::None => bytes.to_vec(),
+        CompressionType::Lz4 => lz4_flex::decompress_size_prepended(bytes)
+            .expect("corrupt lz4 block (checksum verification should have caught this first)"),
+        CompressionType::Zstd { .. } => zstd::decode_all(bytes)
+            .expect("corrupt zstd block (checksum verification should have caught this first)"),
+    }
+}
+
+/// The header persisted alongside a block's (possibly compressed) bytes: the codec needed to
+/// decompress it, and the xxh3 checksum of the bytes as written, so a reader can detect
+/// corruption before it ever reaches the decompressor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BlockHeader {
+    pub(crate) compression: CompressionType,
+    pub(crate) checksum: u64,
+}
+
+/// Encodes a block's raw bytes for storage: compresses them per the provider's configured codec,
+/// then checksums the compressed bytes actually being persisted. Returns the header to store
+/// alongside the payload.
+pub(crate) fn encode_block(config: &ArrowBlockfileProviderConfig, raw: &[u8]) -> (BlockHeader, Vec<u8>) {
+    let payload = compress(config.compression_type, raw);
+    let header = BlockHeader {
+        compression: config.compression_type,
+        checksum: checksum_block_bytes(&payload),
+    };
+    (header, payload)
+}
+
+/// Decodes a block previously written by `encode_block`. When `config.verify_checksum_on_read`
+/// is set, `payload` is checked against `header.checksum` before decompression is attempted, so
+/// corruption is reported as a checksum mismatch rather than a confusing decompressor error.
+pub(crate) fn decode_block(
+    config: &ArrowBlockfileProviderConfig,
+    header: &BlockHeader,
+    payload: &[u8],
+) -> Result<Vec<u8>, BlockChecksumError> {
+    if config.verify_checksum_on_read {
+        verify_block_checksum(payload, header.checksum)?;
+    }
+    Ok(decompress(header.compression, payload))
+}
+
+/// A block encoded by `encode_block_with_parent`: either the full write path from `encode_block`,
+/// or — when compaction's delta encoding is enabled and worthwhile — a diff against the previous
+/// version of this block that `decode_block_with_parent` replays against that parent's raw bytes.
+pub(crate) enum EncodedBlockPayload {
+    Full {
+        header: BlockHeader,
+        bytes: Vec<u8>,
+    },
+    Delta {
+        segments: Vec<DeltaSegment>,
+        // The xxh3 checksum of `segments`' canonical encoding (see `checksum_delta_segments`), so
+        // a corrupted delta is caught before it's replayed against the parent, the same guarantee
+        // `encode_block`/`decode_block` give a `Full` payload.
+        checksum: u64,
+        chain_length: u32,
+    },
+}
+
+// Canonicalizes `segments` into bytes suitable for checksumming: a delta payload has no single
+// "bytes on disk" the way a `Full` payload does, so this stands in for that representation.
+fn checksum_delta_segments(segments: &[DeltaSegment]) -> u64 {
+    let mut canonical = Vec::new();
+    for segment in segments {
+        match segment {
+            DeltaSegment::Copy { parent_offset, len } => {
+                canonical.push(0u8);
+                canonical.extend_from_slice(&parent_offset.to_le_bytes());
+                canonical.extend_from_slice(&len.to_le_bytes());
+            }
+            DeltaSegment::Literal(bytes) => {
+                canonical.push(1u8);
+                canonical.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+                canonical.extend_from_slice(bytes);
+            }
+        }
+    }
+    checksum_block_bytes(&canonical)
+}
+
+/// Compaction's write path: diffs `raw` against `parent` (the block's previous version plus how
+/// long its existing delta chain already is) when `delta_encoding_enabled` and the chain hasn't
+/// hit `max_delta_chain_length`, falling back to a full rewrite (`encode_block`) whenever delta
+/// encoding is disabled, the chain is maxed out, or the diff doesn't save enough to clear
+/// `max_delta_size_ratio`.
+pub(crate) fn encode_block_with_parent(
+    config: &ArrowBlockfileProviderConfig,
+    raw: &[u8],
+    parent: Option<(&[u8], u32)>,
+) -> EncodedBlockPayload {
+    if config.delta_encoding_enabled {
+        if let Some((parent_raw, parent_chain_length)) = parent {
+            if parent_chain_length < config.max_delta_chain_length {
+                let signature = BlockSignature::build(parent_raw);
+                let segments = diff_block(raw, &signature);
+                let ratio = encoded_len(&segments) as f32 / raw.len().max(1) as f32;
+                if ratio <= config.max_delta_size_ratio {
+                    let checksum = checksum_delta_segments(&segments);
+                    return EncodedBlockPayload::Delta {
+                        segments,
+                        checksum,
+                        chain_length: parent_chain_length + 1,
+                    };
+                }
+            }
+        }
+    }
+    let (header, bytes) = encode_block(config, raw);
+    EncodedBlockPayload::Full { header, bytes }
+}
+
+/// Reverses `encode_block_with_parent`. `parent_raw` must be the same parent bytes the block was
+/// diffed against; only required when `payload` is actually delta-encoded.
+pub(crate) fn decode_block_with_parent(
+    config: &ArrowBlockfileProviderConfig,
+    payload: &EncodedBlockPayload,
+    parent_raw: Option<&[u8]>,
+) -> Result<Vec<u8>, BlockChecksumError> {
+    match payload {
+        EncodedBlockPayload::Full { header, bytes } => decode_block(config, header, bytes),
+        EncodedBlockPayload::Delta {
+            segments, checksum, ..
+        } => {
+            if config.verify_checksum_on_read {
+                let computed = checksum_delta_segments(segments);
+                if computed != *checksum {
+                    return Err(BlockChecksumError::BlockChecksumMismatch {
+                        expected: *checksum,
+                        computed,
+                    });
+                }
+            }
+            let parent_raw = parent_raw
+                .expect("a delta-encoded block always has a parent to replay against");
+            Ok(reconstruct_block(parent_raw, segments))
+        }
+    }
+}
+
+/// A single block's write history across successive compactions, as actually produced by the
+/// compaction write path: entry 0 is always `Full` (the block's base version), and every later
+/// entry is either a delta diffed against the raw bytes of the entry immediately before it, or a
+/// `Full` rewrite (delta encoding disabled, the chain at `max_delta_chain_length`, or a diff that
+/// saves too little), which starts a new chain. `decode_block_with_parent` only
+/// replays one delta against an already-reconstructed parent; `BlockChain::resolve` is compaction's
+/// real caller, and walks the chain back to the nearest `Full` entry to reconstruct any version,
+/// not just chain_length == 1.
+pub(crate) struct BlockChain {
+    versions: Vec<EncodedBlockPayload>,
+    // The raw bytes of `versions.last()`, cached so `push` doesn't have to replay the whole
+    // chain back to the nearest `Full` entry just to diff against its own most recent write.
+    // `None` only until the first version is pushed, or if a version is ever appended through
+    // something other than `push` (there's no such path today).
+    last_resolved: Option<Vec<u8>>,
+}
+
+impl BlockChain {
+    pub(crate) fn new() -> Self {
+        Self {
+            versions: Vec::new(),
+            last_resolved: None,
+        }
+    }
+
+    fn chain_length(&self) -> u32 {
+        match self.versions.last() {
+            None | Some(EncodedBlockPayload::Full { .. }) => 0,
+            Some(EncodedBlockPayload::Delta { chain_length, .. }) => *chain_length,
+        }
+    }
+
+    /// Writes `raw` as the next version of this block, diffing it against the chain's current
+    /// last version when delta encoding applies. This is compaction's actual write path: every
+    /// block write goes through here rather than calling `encode_block_with_parent` directly, so
+    /// `chain_length` bookkeeping can never drift from what chain position is really on disk.
+    pub(crate) fn push(
+        &mut self,
+        config: &ArrowBlockfileProviderConfig,
+        raw: &[u8],
+    ) -> Result<(), BlockChecksumError> {
+        let parent = match self.versions.len().checked_sub(1) {
+            Some(last) => {
+                // `last_resolved` is always populated by the previous `push` once there's at
+                // least one version; only fall back to a full replay if the chain was ever
+                // populated some other way.
+                let parent_raw = match &self.last_resolved {
+                    Some(cached) => cached.clone(),
+                    None => self.resolve(config, last)?,
+                };
+                Some((parent_raw, self.chain_length()))
+            }
+            None => None,
+        };
+        let parent_ref = parent.as_ref().map(|(raw, len)| (raw.as_slice(), *len));
+        self.versions
+            .push(encode_block_with_parent(config, raw, parent_ref));
+        self.last_resolved = Some(raw.to_vec());
+        Ok(())
+    }
+
+    /// Reconstructs the raw bytes of the version at `index`, replaying every delta in the chain
+    /// back to the nearest `Full` entry.
+    pub(crate) fn resolve(
+        &self,
+        config: &ArrowBlockfileProviderConfig,
+        index: usize,
+    ) -> Result<Vec<u8>, BlockChecksumError> {
+        match &self.versions[index] {
+            EncodedBlockPayload::Full { header, bytes } => decode_block(config, header, bytes),
+            EncodedBlockPayload::Delta { .. } => {
+                let parent_raw = self.resolve(config, index - 1)?;
+                decode_block_with_parent(config, &self.versions[index], Some(&parent_raw))
+            }
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.versions.len()
+    }
+
+    fn checksum_of(payload: &EncodedBlockPayload) -> u64 {
+        match payload {
+            EncodedBlockPayload::Full { header, .. } => header.checksum,
+            EncodedBlockPayload::Delta { checksum, .. } => *checksum,
+        }
+    }
+
+    /// The checksum of this block's current (most recently written) version — the value that
+    /// feeds the segment's Merkle tree via `segment_merkle_root`/`open_segment_blocks`. `None` for
+    /// a chain nothing has been pushed to yet.
+    pub(crate) fn current_checksum(&self) -> Option<u64> {
+        self.versions.last().map(Self::checksum_of)
+    }
+}
+
+/// Computes a segment's Merkle root from its blocks' current checksums, in `SparseIndex` order.
+/// Called once per flush, after every block in the segment has been written via `BlockChain::push`,
+/// to get the value stored in segment metadata for `open_segment_blocks` to check on open.
+pub(crate) fn segment_merkle_root(blocks_in_sparse_index_order: &[BlockChain]) -> u64 {
+    let checksums: Vec<u64> = blocks_in_sparse_index_order
+        .iter()
+        .filter_map(BlockChain::current_checksum)
+        .collect();
+    segment_root(&checksums)
+}
+
+/// Verifies a segment's integrity when it's opened: rebuilds the Merkle tree from the loaded
+/// blocks' current checksums (in `SparseIndex` order) and checks it against `expected_root`, which
+/// `segment_merkle_root` computed and stored in segment metadata at flush time. Catches a missing
+/// or swapped block even when every individually-loaded block still passes its own checksum.
+pub(crate) fn open_segment_blocks(
+    blocks_in_sparse_index_order: &[BlockChain],
+    expected_root: u64,
+) -> Result<MerkleTree, SegmentIntegrityError> {
+    let checksums: Vec<u64> = blocks_in_sparse_index_order
+        .iter()
+        .filter_map(BlockChain::current_checksum)
+        .collect();
+    open_segment(&checksums, expected_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_codec(compression_type: CompressionType) -> ArrowBlockfileProviderConfig {
+        ArrowBlockfileProviderConfig {
+            max_block_size_bytes: 1024 * 1024,
+            compression_type,
+            verify_checksum_on_read: true,
+            delta_encoding_enabled: false,
+            max_delta_chain_length: 8,
+            max_delta_size_ratio: 0.7,
+        }
+    }
+
+    #[test]
+    fn round_trips_every_codec() {
+        let raw = b"the quick brown fox jumps over the lazy dog".repeat(16);
+        for codec in [
+            CompressionType::None,
+            CompressionType::Lz4,
+            CompressionType::Zstd { level: 3 },
+        ] {
+            let config = config_with_codec(codec);
+            let (header, payload) = encode_block(&config, &raw);
+            let decoded = decode_block(&config, &header, &payload).expect("checksum should match");
+            assert_eq!(decoded, raw, "round trip failed for {codec:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_corrupted_payload_when_verification_enabled() {
+        let config = config_with_codec(CompressionType::None);
+        let (header, mut payload) = encode_block(&config, b"hello world");
+        payload[0] ^= 0xFF;
+
+        let err = decode_block(&config, &header, &payload).unwrap_err();
+        assert!(matches!(
+            err,
+            BlockChecksumError::BlockChecksumMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn skips_verification_when_disabled() {
+        let mut config = config_with_codec(CompressionType::None);
+        let (header, mut payload) = encode_block(&config, b"hello world");
+        payload[0] ^= 0xFF;
+
+        config.verify_checksum_on_read = false;
+        // With verification off, corruption is not caught here; it surfaces downstream instead.
+        assert!(decode_block(&config, &header, &payload).is_ok());
+    }
+
+    fn config_with_delta(delta_encoding_enabled: bool) -> ArrowBlockfileProviderConfig {
+        ArrowBlockfileProviderConfig {
+            max_block_size_bytes: 1024 * 1024,
+            compression_type: CompressionType::None,
+            verify_checksum_on_read: true,
+            delta_encoding_enabled,
+            max_delta_chain_length: 8,
+            max_delta_size_ratio: 0.7,
+        }
+    }
+
+    #[test]
+    fn diffs_against_a_similar_parent() {
+        let config = config_with_delta(true);
+        let parent = b"the quick brown fox jumps over the lazy dog".repeat(8);
+        let mut child = parent.clone();
+        child.extend_from_slice(b" and then ran away");
+
+        let payload = encode_block_with_parent(&config, &child, Some((&parent, 0)));
+        assert!(
+            matches!(payload, EncodedBlockPayload::Delta { .. }),
+            "a small edit to a large parent should encode as a delta"
+        );
+
+        let decoded = decode_block_with_parent(&config, &payload, Some(&parent))
+            .expect("checksum should match");
+        assert_eq!(decoded, child);
+    }
+
+    #[test]
+    fn rejects_a_corrupted_delta_payload_when_verification_enabled() {
+        let config = config_with_delta(true);
+        let parent = b"the quick brown fox jumps over the lazy dog".repeat(8);
+        let mut child = parent.clone();
+        child.extend_from_slice(b" and then ran away");
+
+        let payload = encode_block_with_parent(&config, &child, Some((&parent, 0)));
+        let EncodedBlockPayload::Delta {
+            segments,
+            checksum,
+            chain_length,
+        } = payload
+        else {
+            panic!("a small edit to a large parent should encode as a delta");
+        };
+        let corrupted = EncodedBlockPayload::Delta {
+            segments: vec![DeltaSegment::Literal(b"tampered".to_vec())],
+            checksum,
+            chain_length,
+        };
+
+        let err = decode_block_with_parent(&config, &corrupted, Some(&parent)).unwrap_err();
+        assert!(matches!(
+            err,
+            BlockChecksumError::BlockChecksumMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn falls_back_to_full_rewrite_when_disabled_or_unrelated() {
+        let config = config_with_delta(false);
+        let parent = b"the quick brown fox jumps over the lazy dog".repeat(8);
+        let child = parent.clone();
+
+        let payload = encode_block_with_parent(&config, &child, Some((&parent, 0)));
+        assert!(matches!(payload, EncodedBlockPayload::Full { .. }));
+
+        let config = config_with_delta(true);
+        let unrelated = vec![0u8; parent.len()];
+        let payload = encode_block_with_parent(&config, &unrelated, Some((&parent, 0)));
+        assert!(
+            matches!(payload, EncodedBlockPayload::Full { .. }),
+            "a diff against an unrelated parent saves nothing, so it should fall back to a full rewrite"
+        );
+    }
+
+    #[test]
+    fn resolves_a_chain_of_more_than_one_delta() {
+        let config = config_with_delta(true);
+        let base = b"the quick brown fox jumps over the lazy dog".repeat(8);
+        let mut v1 = base.clone();
+        v1.extend_from_slice(b" and then ran away");
+        let mut v2 = v1.clone();
+        v2.extend_from_slice(b" before coming back for dinner");
+
+        let mut chain = BlockChain::new();
+        chain.push(&config, &base).unwrap();
+        chain.push(&config, &v1).unwrap();
+        chain.push(&config, &v2).unwrap();
+
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain.resolve(&config, 0).unwrap(), base);
+        assert_eq!(chain.resolve(&config, 1).unwrap(), v1);
+        assert_eq!(
+            chain.resolve(&config, 2).unwrap(),
+            v2,
+            "resolving the third version should replay both deltas back to the base"
+        );
+    }
+
+    #[test]
+    fn open_segment_blocks_accepts_a_matching_root_and_rejects_a_swapped_block() {
+        let config = config_with_delta(true);
+        let mut chains = Vec::new();
+        for seed in 0..4u8 {
+            let mut chain = BlockChain::new();
+            chain.push(&config, &vec![seed; 256]).unwrap();
+            chains.push(chain);
+        }
+
+        let root = segment_merkle_root(&chains);
+        assert!(open_segment_blocks(&chains, root).is_ok());
+
+        chains.swap(0, 1);
+        let err = open_segment_blocks(&chains, root).unwrap_err();
+        assert!(matches!(err, SegmentIntegrityError::RootMismatch { .. }));
+    }
+}
@@ -1,5 +1,18 @@
 use serde::Deserialize;
 
+/// The codec used to compress a block's bytes on flush. Recorded in the block header so a reader
+/// can decode blocks written under a previous config without needing to know the provider's
+/// current setting.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) enum CompressionType {
+    #[default]
+    None,
+    Lz4,
+    Zstd {
+        level: i32,
+    },
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub(crate) struct ArrowBlockfileProviderConfig {
     // Note: This provider has two dependent components that
@@ -9,4 +22,40 @@ pub(crate) struct ArrowBlockfileProviderConfig {
     // but the only configuration that is needed is the max_block_size_bytes
     // so for now we just hoid this configuration in the ArrowBlockfileProviderConfig.
     pub(crate) max_block_size_bytes: usize,
+    // The codec the BlockManager uses to compress blocks on flush and decompress them on read.
+    // Defaults to no compression to preserve the existing on-disk format for readers that have
+    // not yet adopted this field.
+    #[serde(default)]
+    pub(crate) compression_type: CompressionType,
+    // Whether the BlockManager should verify a block's xxh3 checksum against its header when
+    // loading it, surfacing `BlockChecksumMismatch` on a mismatch instead of returning corrupt
+    // data to the caller. Defaults to on. Verification is all-or-nothing: `false` skips it on
+    // every load; there is no sampled mode.
+    #[serde(default = "default_verify_checksum_on_read")]
+    pub(crate) verify_checksum_on_read: bool,
+    // Whether compaction should store a new block as a diff against its parent block (see
+    // `block_delta_encoding`) instead of always rewriting it in full. Off by default until the
+    // delta chain bookkeeping has baked in production.
+    #[serde(default)]
+    pub(crate) delta_encoding_enabled: bool,
+    // The longest chain of delta-encoded blocks to allow before forcing a full rewrite, bounding
+    // how many parents a reader must replay to reconstruct a block.
+    #[serde(default = "default_max_delta_chain_length")]
+    pub(crate) max_delta_chain_length: u32,
+    // If a delta's encoded size exceeds this fraction of the new block's full size, fall back to
+    // storing the block in full rather than keeping a delta that saves little or nothing.
+    #[serde(default = "default_max_delta_size_ratio")]
+    pub(crate) max_delta_size_ratio: f32,
+}
+
+fn default_verify_checksum_on_read() -> bool {
+    true
+}
+
+fn default_max_delta_chain_length() -> u32 {
+    8
+}
+
+fn default_max_delta_size_ratio() -> f32 {
+    0.7
 }
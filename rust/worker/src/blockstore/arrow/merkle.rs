@@ -0,0 +1,202 @@
+use chroma_error::{ChromaError, ErrorCodes};
+use thiserror::Error;
+use xxhash_rust::xxh3::xxh3_64;
+
+// Domain-separation tags so a leaf hash and an internal-node hash can never collide even if they
+// happen to combine the same bytes.
+const LEAF_TAG: u8 = 0x00;
+const INTERNAL_TAG: u8 = 0x01;
+
+// The checksum value reserved for padding leaves. Distinct padded leaves still hash identically,
+// which is fine: padding is structural, not data, so every tree with the same leaf count and the
+// same padding needs the same padding hashes to produce a deterministic root.
+const PADDING_LEAF_CHECKSUM: u64 = 0;
+
+fn hash_leaf(checksum: u64) -> u64 {
+    let mut buf = [0u8; 9];
+    buf[0] = LEAF_TAG;
+    buf[1..9].copy_from_slice(&checksum.to_le_bytes());
+    xxh3_64(&buf)
+}
+
+fn hash_internal(left: u64, right: u64) -> u64 {
+    let mut buf = [0u8; 17];
+    buf[0] = INTERNAL_TAG;
+    buf[1..9].copy_from_slice(&left.to_le_bytes());
+    buf[9..17].copy_from_slice(&right.to_le_bytes());
+    xxh3_64(&buf)
+}
+
+/// One step of an inclusion proof: the sibling hash encountered at that level, and whether the
+/// sibling sits to the right of the node being proven (so the verifier knows which order to
+/// combine them in).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProofStep {
+    pub(crate) sibling_hash: u64,
+    pub(crate) sibling_is_right: bool,
+}
+
+/// A Merkle tree over a segment's per-block xxh3 checksums, ordered by the segment's
+/// `SparseIndex`. The root is stored in segment metadata so a reader can detect a missing or
+/// swapped block even when every individually-present block still passes its own checksum.
+///
+/// Leaf counts that are not a power of two are padded with a canonical empty-leaf hash, and the
+/// padded subtrees are folded into the root exactly like real subtrees, so the root is
+/// deterministic regardless of block count.
+pub(crate) struct MerkleTree {
+    leaf_count: usize,
+    // levels[0] is the (padded) leaf hash layer; levels.last() is `[root]`.
+    levels: Vec<Vec<u64>>,
+}
+
+impl MerkleTree {
+    pub(crate) fn build(block_checksums_in_sparse_index_order: &[u64]) -> Self {
+        let leaf_count = block_checksums_in_sparse_index_order.len();
+        let padded_len = leaf_count.max(1).next_power_of_two();
+
+        let mut leaf_hashes: Vec<u64> = block_checksums_in_sparse_index_order
+            .iter()
+            .map(|&checksum| hash_leaf(checksum))
+            .collect();
+        leaf_hashes.resize(padded_len, hash_leaf(PADDING_LEAF_CHECKSUM));
+
+        let mut levels = vec![leaf_hashes];
+        while levels.last().expect("levels is never empty").len() > 1 {
+            let next = levels
+                .last()
+                .expect("levels is never empty")
+                .chunks(2)
+                .map(|pair| hash_internal(pair[0], pair[1]))
+                .collect();
+            levels.push(next);
+        }
+
+        Self { leaf_count, levels }
+    }
+
+    pub(crate) fn root(&self) -> u64 {
+        *self
+            .levels
+            .last()
+            .expect("levels is never empty")
+            .first()
+            .expect("root level always has exactly one hash")
+    }
+
+    /// Produces an inclusion proof for the block at `leaf_index` (its position in the segment's
+    /// `SparseIndex` order), so a reader can validate a single fetched block against the trusted
+    /// root without loading the rest of the segment.
+    pub(crate) fn inclusion_proof(&self, leaf_index: usize) -> Option<Vec<ProofStep>> {
+        if leaf_index >= self.leaf_count {
+            return None;
+        }
+        let mut index = leaf_index;
+        let mut proof = Vec::with_capacity(self.levels.len() - 1);
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling_index = index ^ 1;
+            proof.push(ProofStep {
+                sibling_hash: level[sibling_index],
+                sibling_is_right: sibling_index > index,
+            });
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// Verifies that `block_checksum` is included under `root`, using `proof` produced by
+/// `MerkleTree::inclusion_proof`. The leaf's position is carried by each step's
+/// `sibling_is_right`.
+pub(crate) fn verify_inclusion_proof(
+    block_checksum: u64,
+    proof: &[ProofStep],
+    root: u64,
+) -> bool {
+    let mut hash = hash_leaf(block_checksum);
+    for step in proof {
+        hash = if step.sibling_is_right {
+            hash_internal(hash, step.sibling_hash)
+        } else {
+            hash_internal(step.sibling_hash, hash)
+        };
+    }
+    hash == root
+}
+
+/// Integrity errors raised while opening a segment and checking its Merkle root.
+#[derive(Error, Debug)]
+pub(crate) enum SegmentIntegrityError {
+    #[error("Segment Merkle root mismatch: expected {expected:x}, computed {computed:x}")]
+    RootMismatch { expected: u64, computed: u64 },
+}
+
+impl ChromaError for SegmentIntegrityError {
+    fn code(&self) -> ErrorCodes {
+        match self {
+            SegmentIntegrityError::RootMismatch { .. } => ErrorCodes::DataLoss,
+        }
+    }
+}
+
+/// Called once per flush, after the segment's blocks are written, to compute the root stored in
+/// segment metadata. `open_segment` is the read-side counterpart run when the segment is opened.
+pub(crate) fn segment_root(block_checksums_in_sparse_index_order: &[u64]) -> u64 {
+    MerkleTree::build(block_checksums_in_sparse_index_order).root()
+}
+
+/// Verifies a segment's integrity when it's opened: rebuilds the Merkle tree from its blocks'
+/// checksums (in `SparseIndex` order) and checks the root against `expected_root`, which was
+/// computed by `segment_root` at flush time and stored in segment metadata. This catches a
+/// missing or swapped block even when every individually-loaded block still passes its own
+/// `block_checksum` check.
+pub(crate) fn open_segment(
+    block_checksums_in_sparse_index_order: &[u64],
+    expected_root: u64,
+) -> Result<MerkleTree, SegmentIntegrityError> {
+    let tree = MerkleTree::build(block_checksums_in_sparse_index_order);
+    let computed = tree.root();
+    if computed == expected_root {
+        Ok(tree)
+    } else {
+        Err(SegmentIntegrityError::RootMismatch {
+            expected: expected_root,
+            computed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_segment_accepts_a_matching_root() {
+        let checksums = [11, 22, 33, 44, 55];
+        let root = segment_root(&checksums);
+        assert!(open_segment(&checksums, root).is_ok());
+    }
+
+    #[test]
+    fn open_segment_rejects_a_swapped_block() {
+        let checksums = [11, 22, 33, 44, 55];
+        let root = segment_root(&checksums);
+
+        let mut swapped = checksums;
+        swapped.swap(1, 2);
+
+        let err = open_segment(&swapped, root).unwrap_err();
+        assert!(matches!(err, SegmentIntegrityError::RootMismatch { .. }));
+    }
+
+    #[test]
+    fn inclusion_proof_verifies_against_the_segment_root() {
+        let checksums = [11, 22, 33, 44, 55];
+        let tree = MerkleTree::build(&checksums);
+        let root = tree.root();
+
+        for (leaf_index, &checksum) in checksums.iter().enumerate() {
+            let proof = tree.inclusion_proof(leaf_index).expect("leaf exists");
+            assert!(verify_inclusion_proof(checksum, &proof, root));
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub(crate) mod block_checksum;
+pub(crate) mod block_delta_encoding;
+pub(crate) mod block_manager;
+pub(crate) mod config;
+pub(crate) mod merkle;
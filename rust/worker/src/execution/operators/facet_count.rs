@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chroma_error::{ChromaError, ErrorCodes};
+use chroma_index::metadata::types::MetadataIndexError;
+use chroma_types::{MetadataValue, SignedRoaringBitmap};
+use thiserror::Error;
+use tonic::async_trait;
+use tracing::{trace, Instrument, Span};
+
+use crate::{
+    execution::operator::Operator,
+    segment::{LogMaterializer, LogMaterializerError},
+};
+
+use super::{
+    fetch_log::FetchLogOutput,
+    fetch_segment::{FetchSegmentError, FetchSegmentOutput},
+    filter::{MetadataLogReader, MetadataProvider},
+};
+
+/// The `FacetCountOperator` takes the candidate offset ids produced by a preceding
+/// `FilterOperator` and, for each requested metadata key, counts how many candidates
+/// carry each distinct value of that key. This powers faceted-search UIs layered on
+/// top of Chroma queries.
+#[derive(Clone, Debug)]
+pub struct FacetCountOperator {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct FacetCountInput {
+    pub logs: FetchLogOutput,
+    pub segments: FetchSegmentOutput,
+    pub log_oids: SignedRoaringBitmap,
+    pub compact_oids: SignedRoaringBitmap,
+}
+
+#[derive(Debug)]
+pub struct FacetCountOutput {
+    pub facets: HashMap<String, Vec<(MetadataValue, u64)>>,
+}
+
+#[derive(Error, Debug)]
+pub enum FacetCountError {
+    #[error("Error processing fetch segment output: {0}")]
+    FetchSegment(#[from] FetchSegmentError),
+    #[error("Error reading metadata index: {0}")]
+    IndexError(#[from] MetadataIndexError),
+    #[error("Error materializing log: {0}")]
+    LogMaterializer(#[from] LogMaterializerError),
+}
+
+impl ChromaError for FacetCountError {
+    fn code(&self) -> ErrorCodes {
+        match self {
+            FacetCountError::FetchSegment(e) => e.code(),
+            FacetCountError::IndexError(e) => e.code(),
+            FacetCountError::LogMaterializer(e) => e.code(),
+        }
+    }
+}
+
+#[async_trait]
+impl Operator<FacetCountInput, FacetCountOutput> for FacetCountOperator {
+    type Error = FacetCountError;
+
+    async fn run(&self, input: &FacetCountInput) -> Result<FacetCountOutput, FacetCountError> {
+        trace!("[{}]: {:?}", self.get_name(), input);
+
+        let record_segment_reader = input.segments.record_segment_reader().await?;
+        let materializer =
+            LogMaterializer::new(record_segment_reader.clone(), input.logs.clone(), None);
+        let materialized_logs = materializer
+            .materialize()
+            .instrument(tracing::trace_span!(parent: Span::current(), "Materialize logs"))
+            .await?;
+        let metadata_log_reader = MetadataLogReader::new(&materialized_logs);
+        let log_metadata_provider =
+            MetadataProvider::from_metadata_log_reader(&metadata_log_reader);
+
+        let metadata_segment_reader = input.segments.metadata_segment_reader().await?;
+        let compact_metadata_provider =
+            MetadataProvider::from_metadata_segment_reader(&metadata_segment_reader);
+
+        let mut facets = HashMap::with_capacity(self.keys.len());
+        for key in &self.keys {
+            let log_counts = log_metadata_provider
+                .facet_counts(key, &input.log_oids)
+                .await?;
+            let compact_counts = compact_metadata_provider
+                .facet_counts(key, &input.compact_oids)
+                .await?;
+
+            let mut merged: BTreeMap<MetadataValue, u64> = BTreeMap::new();
+            for (value, count) in log_counts.into_iter().chain(compact_counts) {
+                *merged.entry(value).or_default() += count;
+            }
+            facets.insert(key.clone(), merged.into_iter().collect());
+        }
+
+        Ok(FacetCountOutput { facets })
+    }
+}
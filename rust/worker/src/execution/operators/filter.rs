@@ -93,6 +93,39 @@ impl TryFrom<PreFilterState> for FilterInput {
     }
 }
 
+// Normalizes a document for the log path the same way the compact path's full-text index
+// tokenizes it, so a query matches identically whether it's served from the log or from a
+// compacted segment: lowercase, then split on non-alphanumeric boundaries.
+//
+// This is a hand-rolled approximation, not a call into the real tokenizer: the full-text index's
+// tokenizer lives in `chroma_index`, which doesn't expose it as a reusable function, so the log
+// path can't drive it directly. If `chroma_index` ever exposes its tokenizer (or an equivalent
+// type) publicly, replace this with a direct call to it rather than maintaining a parallel
+// implementation that can silently drift from the real one.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+}
+
+// Computes the exclusive upper bound of a `[prefix, upper)` range by incrementing the last code
+// point of `prefix`. Returns `None` when there is no such upper bound at all (an empty prefix
+// matches everything, and a prefix ending in `char::MAX` has no successor), in which case the
+// caller should fall back to `Bound::Unbounded`. Incrementing past `0xD7FF` lands in the UTF-16
+// surrogate range, which is not a valid `char`; that's not unboundedness, so skip over the
+// surrogate gap to the next real scalar value (`0xE000`) instead of conflating it with the
+// `char::MAX` case.
+fn prefix_upper_bound(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    let last = *chars.last()?;
+    let next = last as u32 + 1;
+    let incremented = char::from_u32(next).or_else(|| {
+        (next == 0xD800).then(|| char::from_u32(0xE000).expect("0xE000 is a valid scalar value"))
+    })?;
+    *chars.last_mut().expect("checked non-empty above") = incremented;
+    Some(chars.into_iter().collect())
+}
+
 /// This sturct provides an abstraction over the materialized logs that is similar to the metadata segment
 pub(crate) struct MetadataLogReader<'me> {
     // This maps metadata keys to `BTreeMap`s, which further map values to offset ids
@@ -101,10 +134,16 @@ pub(crate) struct MetadataLogReader<'me> {
     compact_metadata: HashMap<&'me str, BTreeMap<&'me MetadataValue, RoaringBitmap>>,
     // This maps offset ids to documents, excluding deleted ones
     document: HashMap<u32, &'me str>,
+    // This maps normalized terms to the offset ids of documents containing them, mirroring the
+    // inverted index maintained by the full-text index reader on the compact path
+    term_postings: HashMap<String, RoaringBitmap>,
     // This contains all existing offset ids that are touched by the logs
     touched_oids: RoaringBitmap,
     // This maps user ids to offset ids, excluding deleted ones
     uid_to_oid: HashMap<&'me str, u32>,
+    // This contains every offset id present in the log, excluding deleted ones. Used to resolve
+    // a `SignedRoaringBitmap::Exclude` candidate set into a concrete bitmap.
+    all_oids: RoaringBitmap,
 }
 
 impl<'me> MetadataLogReader<'me> {
@@ -112,8 +151,10 @@ impl<'me> MetadataLogReader<'me> {
         let mut compact_metadata: HashMap<_, BTreeMap<&MetadataValue, RoaringBitmap>> =
             HashMap::new();
         let mut document = HashMap::new();
+        let mut term_postings: HashMap<String, RoaringBitmap> = HashMap::new();
         let mut touched_oids = RoaringBitmap::new();
         let mut uid_to_oid = HashMap::new();
+        let mut all_oids = RoaringBitmap::new();
         for (log, _) in logs.iter() {
             if !matches!(
                 log.final_operation,
@@ -125,6 +166,7 @@ impl<'me> MetadataLogReader<'me> {
                 log.final_operation,
                 MaterializedLogOperation::DeleteExisting
             ) {
+                all_oids.insert(log.offset_id);
                 uid_to_oid.insert(log.merged_user_id_ref(), log.offset_id);
                 let log_meta = log.merged_metadata_ref();
                 for (key, val) in log_meta.into_iter() {
@@ -137,16 +179,47 @@ impl<'me> MetadataLogReader<'me> {
                 }
                 if let Some(doc) = log.merged_document_ref() {
                     document.insert(log.offset_id, doc);
+                    for term in tokenize(doc) {
+                        term_postings.entry(term).or_default().insert(log.offset_id);
+                    }
                 }
             }
         }
         Self {
             compact_metadata,
             document,
+            term_postings,
             touched_oids,
             uid_to_oid,
+            all_oids,
+        }
+    }
+
+    // Resolves a signed candidate set into a concrete bitmap of log offset ids.
+    pub(crate) fn materialize(&self, candidates: &SignedRoaringBitmap) -> RoaringBitmap {
+        match candidates {
+            SignedRoaringBitmap::Include(set) => set.clone(),
+            SignedRoaringBitmap::Exclude(set) => &self.all_oids - set,
         }
     }
+
+    // Returns the distinct values of `key` present in the log, in ascending or descending key
+    // order, each paired with its full offset id bitmap (not yet intersected with candidates).
+    pub(crate) fn ordered_values(
+        &self,
+        key: &str,
+        ascending: bool,
+    ) -> Vec<(&MetadataValue, &RoaringBitmap)> {
+        let Some(btm) = self.compact_metadata.get(key) else {
+            return Vec::new();
+        };
+        if ascending {
+            btm.iter().map(|(k, v)| (*k, v)).collect()
+        } else {
+            btm.iter().rev().map(|(k, v)| (*k, v)).collect()
+        }
+    }
+
     pub(crate) fn get(
         &self,
         key: &str,
@@ -154,12 +227,25 @@ impl<'me> MetadataLogReader<'me> {
         op: &PrimitiveOperator,
     ) -> Result<RoaringBitmap, FilterError> {
         if let Some(btm) = self.compact_metadata.get(key) {
+            let upper_owned;
             let bounds = match op {
                 PrimitiveOperator::Equal => (Bound::Included(&val), Bound::Included(&val)),
                 PrimitiveOperator::GreaterThan => (Bound::Excluded(&val), Bound::Unbounded),
                 PrimitiveOperator::GreaterThanOrEqual => (Bound::Included(&val), Bound::Unbounded),
                 PrimitiveOperator::LessThan => (Bound::Unbounded, Bound::Excluded(&val)),
                 PrimitiveOperator::LessThanOrEqual => (Bound::Unbounded, Bound::Included(&val)),
+                PrimitiveOperator::StartsWith => {
+                    let MetadataValue::Str(prefix) = val else {
+                        return Ok(RoaringBitmap::new());
+                    };
+                    match prefix_upper_bound(prefix) {
+                        Some(upper) => {
+                            upper_owned = MetadataValue::Str(upper);
+                            (Bound::Included(&val), Bound::Excluded(&upper_owned))
+                        }
+                        None => (Bound::Included(&val), Bound::Unbounded),
+                    }
+                }
                 PrimitiveOperator::NotEqual => unreachable!(
                     "Inequality filter should be handled above the metadata provider level"
                 ),
@@ -173,11 +259,76 @@ impl<'me> MetadataLogReader<'me> {
         }
     }
 
+    // Tests whether a single offset id matches `key op val`, without materializing the full
+    // matching bitmap. Used by the adaptive And-evaluation strategy once few candidates remain.
+    pub(crate) fn contains(
+        &self,
+        oid: u32,
+        key: &str,
+        val: &MetadataValue,
+        op: &PrimitiveOperator,
+    ) -> bool {
+        let Some(btm) = self.compact_metadata.get(key) else {
+            return false;
+        };
+        let upper_owned;
+        let bounds = match op {
+            PrimitiveOperator::Equal => (Bound::Included(&val), Bound::Included(&val)),
+            PrimitiveOperator::GreaterThan => (Bound::Excluded(&val), Bound::Unbounded),
+            PrimitiveOperator::GreaterThanOrEqual => (Bound::Included(&val), Bound::Unbounded),
+            PrimitiveOperator::LessThan => (Bound::Unbounded, Bound::Excluded(&val)),
+            PrimitiveOperator::LessThanOrEqual => (Bound::Unbounded, Bound::Included(&val)),
+            PrimitiveOperator::StartsWith => {
+                let MetadataValue::Str(prefix) = val else {
+                    return false;
+                };
+                match prefix_upper_bound(prefix) {
+                    Some(upper) => {
+                        upper_owned = MetadataValue::Str(upper);
+                        (Bound::Included(&val), Bound::Excluded(&upper_owned))
+                    }
+                    None => (Bound::Included(&val), Bound::Unbounded),
+                }
+            }
+            PrimitiveOperator::NotEqual => unreachable!(
+                "Inequality filter should be handled above the metadata provider level"
+            ),
+        };
+        btm.range::<&MetadataValue, _>(bounds)
+            .any(|(_, bitmap)| bitmap.contains(oid))
+    }
+
     pub(crate) fn search_user_ids(&self, uids: &[&str]) -> RoaringBitmap {
         uids.iter()
             .filter_map(|uid| self.uid_to_oid.get(uid))
             .collect()
     }
+
+    // Enumerates the distinct values of `key` seen in the log, paired with the cardinality of
+    // their intersection with `candidates`. Used by the `FacetCountOperator` to break down the
+    // final candidate set by metadata value.
+    pub(crate) fn facet_counts(
+        &self,
+        key: &str,
+        candidates: &SignedRoaringBitmap,
+    ) -> Vec<(&MetadataValue, u64)> {
+        let Some(btm) = self.compact_metadata.get(key) else {
+            return Vec::new();
+        };
+        let universe = btm
+            .values()
+            .fold(RoaringBitmap::new(), |acc, bitmap| acc | bitmap);
+        let materialized = match candidates {
+            SignedRoaringBitmap::Include(set) => universe & set,
+            SignedRoaringBitmap::Exclude(set) => universe - set,
+        };
+        btm.iter()
+            .filter_map(|(val, bitmap)| {
+                let count = (&materialized & bitmap).len();
+                (count > 0).then_some((*val, count))
+            })
+            .collect()
+    }
 }
 
 pub(crate) enum MetadataProvider<'me> {
@@ -209,11 +360,31 @@ impl<'me> MetadataProvider<'me> {
                     Ok(RoaringBitmap::new())
                 }
             }
-            MetadataProvider::Log(metadata_log_reader) => Ok(metadata_log_reader
-                .document
-                .iter()
-                .filter_map(|(oid, doc)| doc.contains(query).then_some(oid))
-                .collect()),
+            MetadataProvider::Log(metadata_log_reader) => {
+                let mut terms = tokenize(query);
+                let result = match terms.next() {
+                    Some(first_term) => {
+                        let mut result = metadata_log_reader
+                            .term_postings
+                            .get(&first_term)
+                            .cloned()
+                            .unwrap_or_default();
+                        for term in terms {
+                            let postings = metadata_log_reader
+                                .term_postings
+                                .get(&term)
+                                .cloned()
+                                .unwrap_or_default();
+                            result &= postings;
+                        }
+                        result
+                    }
+                    // A query with no extractable terms matches nothing, consistent with the
+                    // full-text index tokenizing it down to an empty term set
+                    None => RoaringBitmap::new(),
+                };
+                Ok(result)
+            }
         }
     }
 
@@ -252,6 +423,19 @@ impl<'me> MetadataProvider<'me> {
                         PrimitiveOperator::GreaterThanOrEqual => Ok(reader.gte(key, kw).await?),
                         PrimitiveOperator::LessThan => Ok(reader.lt(key, kw).await?),
                         PrimitiveOperator::LessThanOrEqual => Ok(reader.lte(key, kw).await?),
+                        PrimitiveOperator::StartsWith => {
+                            let MetadataValue::Str(prefix) = val else {
+                                return Ok(RoaringBitmap::new());
+                            };
+                            let from_prefix = reader.gte(key, kw).await?;
+                            match prefix_upper_bound(prefix) {
+                                Some(upper) => {
+                                    let upper_kw = &upper.as_str().into();
+                                    Ok(from_prefix & reader.lt(key, upper_kw).await?)
+                                }
+                                None => Ok(from_prefix),
+                            }
+                        }
                         PrimitiveOperator::NotEqual => unreachable!(
                             "Inequality filter should be handled above the metadata provider level"
                         ),
@@ -263,6 +447,133 @@ impl<'me> MetadataProvider<'me> {
             MetadataProvider::Log(metadata_log_reader) => metadata_log_reader.get(key, val, op),
         }
     }
+
+    // Enumerates the distinct values of `key`, paired with the cardinality of their intersection
+    // with `candidates`, for the `FacetCountOperator`.
+    pub(crate) async fn facet_counts(
+        &self,
+        key: &str,
+        candidates: &SignedRoaringBitmap,
+    ) -> Result<Vec<(MetadataValue, u64)>, FilterError> {
+        match self {
+            MetadataProvider::CompactData(metadata_segment_reader) => {
+                let mut values = Vec::new();
+                if let Some(reader) = metadata_segment_reader.bool_metadata_index_reader.as_ref()
+                {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Bool(value), bitmap));
+                    }
+                }
+                if let Some(reader) = metadata_segment_reader.u32_metadata_index_reader.as_ref() {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Int(value as i64), bitmap));
+                    }
+                }
+                if let Some(reader) = metadata_segment_reader.f32_metadata_index_reader.as_ref() {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Float(value as f64), bitmap));
+                    }
+                }
+                if let Some(reader) = metadata_segment_reader
+                    .string_metadata_index_reader
+                    .as_ref()
+                {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Str(value), bitmap));
+                    }
+                }
+                let materialized = match candidates {
+                    SignedRoaringBitmap::Include(set) => set.clone(),
+                    SignedRoaringBitmap::Exclude(set) => {
+                        let universe = values
+                            .iter()
+                            .fold(RoaringBitmap::new(), |acc, (_, bitmap)| acc | bitmap);
+                        universe - set
+                    }
+                };
+                Ok(values
+                    .into_iter()
+                    .filter_map(|(value, bitmap)| {
+                        let count = (&materialized & &bitmap).len();
+                        (count > 0).then_some((value, count))
+                    })
+                    .collect())
+            }
+            MetadataProvider::Log(metadata_log_reader) => Ok(metadata_log_reader
+                .facet_counts(key, candidates)
+                .into_iter()
+                .map(|(value, count)| (value.clone(), count))
+                .collect()),
+        }
+    }
+
+    // Returns the distinct values of `key`, in ascending or descending order, each paired with
+    // its full (pre-candidate-filtering) offset id bitmap. Used by the `SortOperator`.
+    pub(crate) async fn ordered_values(
+        &self,
+        key: &str,
+        ascending: bool,
+    ) -> Result<Vec<(MetadataValue, RoaringBitmap)>, FilterError> {
+        match self {
+            MetadataProvider::CompactData(metadata_segment_reader) => {
+                let mut values = Vec::new();
+                if let Some(reader) = metadata_segment_reader.bool_metadata_index_reader.as_ref()
+                {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Bool(value), bitmap));
+                    }
+                }
+                if let Some(reader) = metadata_segment_reader.u32_metadata_index_reader.as_ref() {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Int(value as i64), bitmap));
+                    }
+                }
+                if let Some(reader) = metadata_segment_reader.f32_metadata_index_reader.as_ref() {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Float(value as f64), bitmap));
+                    }
+                }
+                if let Some(reader) = metadata_segment_reader
+                    .string_metadata_index_reader
+                    .as_ref()
+                {
+                    for (value, bitmap) in reader.get_all(key).await? {
+                        values.push((MetadataValue::Str(value), bitmap));
+                    }
+                }
+                values.sort_by(|(a, _), (b, _)| a.cmp(b));
+                if !ascending {
+                    values.reverse();
+                }
+                Ok(values)
+            }
+            MetadataProvider::Log(metadata_log_reader) => Ok(metadata_log_reader
+                .ordered_values(key, ascending)
+                .into_iter()
+                .map(|(value, bitmap)| (value.clone(), bitmap.clone()))
+                .collect()),
+        }
+    }
+
+    // Resolves a signed candidate set into a concrete bitmap. On the log path this uses the
+    // exact offset id universe; on the compact path, where no such universe is tracked here, an
+    // `Exclude` candidate set is resolved against the caller-supplied `universe_hint` (the
+    // `SortOperator` passes every offset id in the record segment).
+    pub(crate) fn materialize(
+        &self,
+        candidates: &SignedRoaringBitmap,
+        universe_hint: &RoaringBitmap,
+    ) -> RoaringBitmap {
+        match self {
+            MetadataProvider::CompactData(_) => match candidates {
+                SignedRoaringBitmap::Include(set) => set.clone(),
+                SignedRoaringBitmap::Exclude(set) => universe_hint - set,
+            },
+            MetadataProvider::Log(metadata_log_reader) => {
+                metadata_log_reader.materialize(candidates)
+            }
+        }
+    }
 }
 
 pub(crate) trait RoaringMetadataFilter<'me> {
@@ -314,7 +625,8 @@ impl<'me> RoaringMetadataFilter<'me> for DirectWhereComparison {
                     | PrimitiveOperator::GreaterThan
                     | PrimitiveOperator::GreaterThanOrEqual
                     | PrimitiveOperator::LessThan
-                    | PrimitiveOperator::LessThanOrEqual => SignedRoaringBitmap::Include(
+                    | PrimitiveOperator::LessThanOrEqual
+                    | PrimitiveOperator::StartsWith => SignedRoaringBitmap::Include(
                         meta_provider
                             .filter_by_metadata(&self.key, metadata_value, primitive_operator)
                             .await?,
@@ -375,22 +687,118 @@ impl<'me> RoaringMetadataFilter<'me> for DirectDocumentComparison {
     }
 }
 
+// Once the running conjunction's candidate set is concrete and drops below this size, the
+// remaining conjuncts are tested per-surviving-oid instead of materializing a full bitmap and
+// intersecting it, which is wasteful when only a handful of candidates remain.
+const ADAPTIVE_CANDIDATE_THRESHOLD: u64 = 1000;
+
+impl WhereChildren {
+    // Direct equality comparisons tend to be the most selective and cheapest to resolve, so
+    // evaluate them ahead of ranges, set membership, document scans, and nested clauses.
+    fn cheapest_first_order(&self) -> Vec<&Where> {
+        let mut order: Vec<&Where> = self.children.iter().collect();
+        order.sort_by_key(|child| match child {
+            Where::DirectWhereComparison(DirectWhereComparison {
+                comparison: WhereComparison::Primitive(PrimitiveOperator::Equal, _),
+                ..
+            }) => 0,
+            Where::DirectWhereComparison(DirectWhereComparison {
+                comparison: WhereComparison::Primitive(_, _),
+                ..
+            }) => 1,
+            Where::DirectWhereComparison(DirectWhereComparison {
+                comparison: WhereComparison::Set(_, _),
+                ..
+            }) => 2,
+            Where::DirectWhereDocumentComparison(_) => 3,
+            Where::WhereChildren(_) => 4,
+        });
+        order
+    }
+
+    async fn eval_and<'me>(
+        &'me self,
+        meta_provider: &MetadataProvider<'me>,
+    ) -> Result<SignedRoaringBitmap, FilterError> {
+        let mut children = self.cheapest_first_order().into_iter();
+        let mut running = SignedRoaringBitmap::full();
+        while let Some(child) = children.next() {
+            running = running & child.eval(meta_provider).await?;
+            // Check the threshold only after applying `child`, so a conjunct that trips it is
+            // still folded into `running` before we switch the *remaining* conjuncts to probing.
+            if let SignedRoaringBitmap::Include(candidates) = &running {
+                if candidates.len() < ADAPTIVE_CANDIDATE_THRESHOLD {
+                    return Self::eval_and_by_probing(candidates.clone(), children, meta_provider)
+                        .await;
+                }
+            }
+        }
+        Ok(running)
+    }
+
+    // Tests each of `remaining`'s primitive equality/range conjuncts against the surviving
+    // `candidates`. On the log path, `MetadataLogReader::contains` is a direct map probe with no
+    // full-bitmap materialization, so testing it once per surviving oid is genuinely cheaper than
+    // materializing and intersecting. The compact path's index has no such per-oid membership
+    // probe — only range scans that materialize a full bitmap — so probing it per oid would redo
+    // that materialization once per surviving candidate; there we materialize each conjunct's
+    // bitmap exactly once and intersect, same as the non-adaptive path. Set comparisons, document
+    // comparisons, and nested clauses always fall back to the standard materialize path.
+    async fn eval_and_by_probing<'me>(
+        mut candidates: RoaringBitmap,
+        remaining: impl Iterator<Item = &'me Where>,
+        meta_provider: &MetadataProvider<'me>,
+    ) -> Result<SignedRoaringBitmap, FilterError> {
+        for child in remaining {
+            if let Where::DirectWhereComparison(DirectWhereComparison {
+                key,
+                comparison: WhereComparison::Primitive(op, val),
+            }) = child
+            {
+                if !matches!(op, PrimitiveOperator::NotEqual) {
+                    candidates = match meta_provider {
+                        MetadataProvider::Log(metadata_log_reader) => {
+                            let mut survivors = RoaringBitmap::new();
+                            for oid in candidates.iter() {
+                                if metadata_log_reader.contains(oid, key, val, op) {
+                                    survivors.insert(oid);
+                                }
+                            }
+                            survivors
+                        }
+                        MetadataProvider::CompactData(_) => {
+                            let matches = meta_provider.filter_by_metadata(key, val, op).await?;
+                            &candidates & &matches
+                        }
+                    };
+                    continue;
+                }
+            }
+            candidates = match child.eval(meta_provider).await? {
+                SignedRoaringBitmap::Include(set) => &candidates & &set,
+                SignedRoaringBitmap::Exclude(set) => &candidates - &set,
+            };
+        }
+        Ok(SignedRoaringBitmap::Include(candidates))
+    }
+}
+
 impl<'me> RoaringMetadataFilter<'me> for WhereChildren {
     async fn eval(
         &'me self,
         meta_provider: &MetadataProvider<'me>,
     ) -> Result<SignedRoaringBitmap, FilterError> {
-        let mut child_evals = Vec::new();
-        for child in &self.children {
-            child_evals.push(child.eval(meta_provider).await?);
-        }
         match self.operator {
-            BooleanOperator::And => Ok(child_evals
-                .into_iter()
-                .fold(SignedRoaringBitmap::full(), BitAnd::bitand)),
-            BooleanOperator::Or => Ok(child_evals
-                .into_iter()
-                .fold(SignedRoaringBitmap::empty(), BitOr::bitor)),
+            BooleanOperator::And => self.eval_and(meta_provider).await,
+            BooleanOperator::Or => {
+                let mut child_evals = Vec::new();
+                for child in &self.children {
+                    child_evals.push(child.eval(meta_provider).await?);
+                }
+                Ok(child_evals
+                    .into_iter()
+                    .fold(SignedRoaringBitmap::empty(), BitOr::bitor))
+            }
         }
     }
 }
@@ -468,3 +876,64 @@ impl Operator<FilterInput, FilterOutput> for FilterOperator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // These tests cannot compare `tokenize` against the real full-text index tokenizer: that
+    // tokenizer lives in `chroma_index`, which this crate depends on only through
+    // `MetadataSegmentReader`'s opaque `full_text_index_reader.search`, not through a reusable
+    // tokenizer function or type — and the `chroma_test`-based compacted-segment harness used by
+    // `benches/filter.rs` isn't available as a dependency here either. Short of either of those
+    // becoming available, the best this suite can do is pin `tokenize`'s exact semantics
+    // (lowercase, split on non-alphanumeric boundaries) so it stays self-consistent and any
+    // future change to it is a deliberate, reviewed one — not a guarantee of parity with the real
+    // index.
+    #[test]
+    fn tokenize_lowercases_and_splits_on_non_alphanumeric_boundaries() {
+        let tokens: Vec<_> = tokenize("Dogs don't bark at the Moon!").collect();
+        assert_eq!(
+            tokens,
+            vec!["dogs", "don", "t", "bark", "at", "the", "moon"]
+        );
+    }
+
+    #[test]
+    fn tokenize_does_not_match_mid_word_substrings() {
+        let tokens: std::collections::HashSet<_> =
+            tokenize("my doggy ate the homework").collect();
+        assert!(!tokens.contains("dog"));
+        assert!(tokens.contains("doggy"));
+    }
+
+    #[test]
+    fn tokenize_ignores_repeated_separators_and_empty_runs() {
+        let tokens: Vec<_> = tokenize("  hello,,world -- 2024  ").collect();
+        assert_eq!(tokens, vec!["hello", "world", "2024"]);
+    }
+
+    #[test]
+    fn prefix_upper_bound_skips_the_utf16_surrogate_gap() {
+        let prefix = format!("a{}", char::from_u32(0xD7FF).unwrap());
+        let upper = prefix_upper_bound(&prefix).expect("0xD7FF has a successor outside the gap");
+        assert_eq!(upper.chars().last(), Some(char::from_u32(0xE000).unwrap()));
+    }
+
+    #[test]
+    fn prefix_upper_bound_is_unbounded_only_at_char_max() {
+        let prefix = format!("a{}", char::MAX);
+        assert_eq!(prefix_upper_bound(&prefix), None);
+    }
+
+    // `filter_by_document`'s log branch looks each query term up in postings built by running
+    // `tokenize` over documents; this checks that a query's terms, tokenized the same way, all
+    // appear among the terms of a document that contains them despite case and punctuation.
+    #[test]
+    fn query_and_document_tokenize_the_same_way() {
+        let query_terms: Vec<_> = tokenize("Quick Fox").collect();
+        let document_terms: std::collections::HashSet<_> =
+            tokenize("The quick, brown fox jumps.").collect();
+        assert!(query_terms.iter().all(|term| document_terms.contains(term)));
+    }
+}
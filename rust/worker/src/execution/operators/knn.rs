@@ -31,8 +31,39 @@ pub fn normalize(vector: &[f32]) -> Vec<f32> {
     vector.iter().map(|x| x / (norm + 1e-32)).collect()
 }
 
+// The three valid ways a knn query can bound its result set. Modeled as an enum rather than
+// `fetch: Option<u32>` plus `max_distance: Option<f32>` so that "neither bound set" can't be
+// constructed at all, instead of being rejected with a runtime `.expect()` deep in knn_log.rs.
+#[derive(Clone, Copy, Debug)]
+pub enum KnnBound {
+    /// The fixed top-`fetch` nearest neighbors, uncapped by distance.
+    TopK { fetch: u32 },
+    /// Every neighbor within `max_distance`, uncapped by count.
+    Radius { max_distance: f32 },
+    /// Every neighbor within `max_distance`, further capped to the closest `fetch`.
+    RadiusTopK { fetch: u32, max_distance: f32 },
+}
+
+impl KnnBound {
+    pub fn fetch(&self) -> Option<u32> {
+        match self {
+            KnnBound::TopK { fetch } | KnnBound::RadiusTopK { fetch, .. } => Some(*fetch),
+            KnnBound::Radius { .. } => None,
+        }
+    }
+
+    pub fn max_distance(&self) -> Option<f32> {
+        match self {
+            KnnBound::Radius { max_distance } | KnnBound::RadiusTopK { max_distance, .. } => {
+                Some(*max_distance)
+            }
+            KnnBound::TopK { .. } => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct KnnOperator {
     pub embedding: Vec<f32>,
-    pub fetch: u32,
+    pub bound: KnnBound,
 }
\ No newline at end of file
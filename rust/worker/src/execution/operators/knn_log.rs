@@ -3,6 +3,7 @@ use std::collections::BinaryHeap;
 use chroma_distance::DistanceFunction;
 use chroma_error::ChromaError;
 use chroma_types::{MaterializedLogOperation, SignedRoaringBitmap};
+use rayon::prelude::*;
 use thiserror::Error;
 use tonic::async_trait;
 
@@ -11,7 +12,7 @@ use crate::{
         operator::Operator,
         utils::{normalize, Distance},
     },
-    segment::{LogMaterializer, LogMaterializerError},
+    segment::{LogMaterializer, LogMaterializerError, MaterializedLogRecord},
 };
 
 use super::{
@@ -20,6 +21,10 @@ use super::{
     knn::KnnOperator,
 };
 
+// Below this many candidates, the overhead of splitting work across threads outweighs the
+// benefit of parallelizing the distance computation.
+const PARALLEL_CANDIDATE_THRESHOLD: usize = 4096;
+
 #[derive(Debug)]
 struct KnnLogInput {
     logs: FetchLogOutput,
@@ -53,6 +58,64 @@ impl ChromaError for KnnLogError {
     }
 }
 
+fn is_candidate(log: &MaterializedLogRecord, log_oids: &SignedRoaringBitmap) -> bool {
+    !matches!(
+        log.final_operation,
+        MaterializedLogOperation::DeleteExisting
+    ) && match log_oids {
+        SignedRoaringBitmap::Include(rbm) => rbm.contains(log.offset_id),
+        SignedRoaringBitmap::Exclude(rbm) => !rbm.contains(log.offset_id),
+    }
+}
+
+fn distance_to(log: &MaterializedLogRecord, metric: &DistanceFunction, target: &[f32]) -> Distance {
+    let log_vector;
+    let log_embedding = if let DistanceFunction::Cosine = metric {
+        log_vector = normalize(log.merged_embeddings());
+        &log_vector
+    } else {
+        log.merged_embeddings()
+    };
+    Distance {
+        oid: log.offset_id,
+        measure: metric.distance(target, log_embedding),
+    }
+}
+
+// Pushes `distance` into `heap` if it is smaller than the current farthest entry, keeping the
+// heap bounded to `capacity`.
+fn push_bounded(heap: &mut BinaryHeap<Distance>, capacity: usize, distance: Distance) {
+    if heap.len() < capacity {
+        heap.push(distance);
+    } else if let Some(far) = heap.peek() {
+        if &distance < far {
+            heap.pop();
+            heap.push(distance);
+        }
+    }
+}
+
+// Merges the per-chunk bounded heaps into the overall top-`fetch` distances, ordered by
+// `Distance::total_cmp` on the measure alone. When several candidates tie at the cut-off
+// distance, which of them are kept can differ from the single-heap sequential scan.
+fn k_way_merge(heaps: Vec<BinaryHeap<Distance>>, fetch: usize) -> Vec<Distance> {
+    let mut sorted: Vec<Vec<Distance>> = heaps.into_iter().map(BinaryHeap::into_sorted_vec).collect();
+    let mut merged = Vec::with_capacity(fetch);
+    while merged.len() < fetch {
+        let smallest = sorted
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.first().map(|d| (i, d)))
+            .min_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(i, _)| i);
+        match smallest {
+            Some(i) => merged.push(sorted[i].remove(0)),
+            None => break,
+        }
+    }
+    merged
+}
+
 #[async_trait]
 impl Operator<KnnLogInput, KnnLogOutput> for KnnOperator {
     type Error = KnnLogError;
@@ -74,41 +137,69 @@ impl Operator<KnnLogInput, KnnLogOutput> for KnnOperator {
             &self.embedding
         };
 
-        let mut heap = BinaryHeap::with_capacity(self.fetch as usize);
-
-        for (log, _) in logs.iter() {
-            if !matches!(
-                log.final_operation,
-                MaterializedLogOperation::DeleteExisting
-            ) && match &input.log_oids {
-                SignedRoaringBitmap::Include(rbm) => rbm.contains(log.offset_id),
-                SignedRoaringBitmap::Exclude(rbm) => !rbm.contains(log.offset_id),
-            } {
-                let log_vector;
-                let log_embedding = if let DistanceFunction::Cosine = metric {
-                    log_vector = normalize(log.merged_embeddings());
-                    &log_vector
-                } else {
-                    log.merged_embeddings()
-                };
-
-                let distance = Distance {
-                    oid: log.offset_id,
-                    measure: metric.distance(target_embedding, log_embedding),
-                };
-                if heap.len() < self.fetch as usize {
-                    heap.push(distance);
-                } else if let Some(far) = heap.peek() {
-                    if &distance < far {
-                        heap.pop();
-                        heap.push(distance);
-                    }
+        let candidates: Vec<_> = logs
+            .iter()
+            .map(|(log, _)| log)
+            .filter(|log| is_candidate(log, &input.log_oids))
+            .collect();
+
+        let fetch = self.bound.fetch().map(|fetch| fetch as usize);
+        let parallel = candidates.len() >= PARALLEL_CANDIDATE_THRESHOLD;
+
+        let mut distances = if let Some(max_distance) = self.bound.max_distance() {
+            // Radius search: no bounded heap, just every neighbor within `max_distance`.
+            let mut within_radius: Vec<Distance> = if parallel {
+                candidates
+                    .par_iter()
+                    .map(|log| distance_to(log, &metric, target_embedding))
+                    .filter(|distance| distance.measure <= max_distance)
+                    .collect()
+            } else {
+                candidates
+                    .iter()
+                    .map(|log| distance_to(log, &metric, target_embedding))
+                    .filter(|distance| distance.measure <= max_distance)
+                    .collect()
+            };
+            within_radius.sort();
+            within_radius
+        } else {
+            // `KnnBound` guarantees a top-k-only or radius-top-k query always carries a `fetch`,
+            // so this is unreachable rather than a runtime invariant to enforce here.
+            let fetch = fetch.expect("KnnBound::TopK and RadiusTopK always carry a fetch");
+            if parallel {
+                let chunk_count = rayon::current_num_threads().max(1);
+                let chunk_size = candidates.len().div_ceil(chunk_count).max(1);
+                let partial_heaps: Vec<BinaryHeap<Distance>> = candidates
+                    .par_chunks(chunk_size)
+                    .map(|chunk| {
+                        let mut heap = BinaryHeap::with_capacity(fetch);
+                        for log in chunk {
+                            push_bounded(&mut heap, fetch, distance_to(log, &metric, target_embedding));
+                        }
+                        heap
+                    })
+                    .collect();
+                k_way_merge(partial_heaps, fetch)
+            } else {
+                let mut heap = BinaryHeap::with_capacity(fetch);
+                for log in candidates {
+                    push_bounded(&mut heap, fetch, distance_to(log, &metric, target_embedding));
                 }
+                heap.into_sorted_vec()
             }
+        };
+
+        // Only a caller-supplied `fetch` caps the result count. A pure-radius query (`fetch:
+        // None`) returns every neighbor within `max_distance` uncapped instead of being silently
+        // truncated to zero.
+        if let Some(fetch) = fetch {
+            distances.truncate(fetch);
         }
+
         Ok(KnnLogOutput {
             logs: input.logs.clone(),
-            distances: heap.into_sorted_vec(),
+            distances,
         })
     }
 }
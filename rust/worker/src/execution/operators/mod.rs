@@ -0,0 +1,5 @@
+pub(crate) mod facet_count;
+pub(crate) mod filter;
+pub(crate) mod knn;
+pub(crate) mod knn_log;
+pub(crate) mod sort;
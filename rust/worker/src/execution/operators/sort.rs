@@ -0,0 +1,185 @@
+use chroma_error::{ChromaError, ErrorCodes};
+use chroma_index::metadata::types::MetadataIndexError;
+use chroma_types::{MetadataValue, SignedRoaringBitmap};
+use roaring::RoaringBitmap;
+use thiserror::Error;
+use tonic::async_trait;
+use tracing::{trace, Instrument, Span};
+
+use crate::{
+    execution::operator::Operator,
+    segment::{LogMaterializer, LogMaterializerError},
+};
+
+use super::{
+    fetch_log::FetchLogOutput,
+    fetch_segment::{FetchSegmentError, FetchSegmentOutput},
+    filter::{MetadataLogReader, MetadataProvider},
+};
+
+/// The `SortOperator` orders the candidate offset ids produced by a preceding `FilterOperator`
+/// by a metadata key, instead of by relevance. Candidates missing the key are grouped at the end
+/// in offset id order.
+#[derive(Clone, Debug)]
+pub struct SortOperator {
+    pub key: String,
+    pub ascending: bool,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct SortInput {
+    pub logs: FetchLogOutput,
+    pub segments: FetchSegmentOutput,
+    pub log_oids: SignedRoaringBitmap,
+    pub compact_oids: SignedRoaringBitmap,
+}
+
+#[derive(Debug)]
+pub struct SortOutput {
+    pub offset_ids: Vec<u32>,
+}
+
+#[derive(Error, Debug)]
+pub enum SortError {
+    #[error("Error processing fetch segment output: {0}")]
+    FetchSegment(#[from] FetchSegmentError),
+    #[error("Error reading metadata index: {0}")]
+    IndexError(#[from] MetadataIndexError),
+    #[error("Error materializing log: {0}")]
+    LogMaterializer(#[from] LogMaterializerError),
+}
+
+impl ChromaError for SortError {
+    fn code(&self) -> ErrorCodes {
+        match self {
+            SortError::FetchSegment(e) => e.code(),
+            SortError::IndexError(e) => e.code(),
+            SortError::LogMaterializer(e) => e.code(),
+        }
+    }
+}
+
+// Intersects each of `buckets` (already in the desired order) with `candidates`, dropping
+// buckets that become empty, and tracks the union of all matched ids so the caller can later
+// compute which candidates were left without a value for the sort key.
+fn restrict_to_candidates(
+    buckets: Vec<(MetadataValue, RoaringBitmap)>,
+    candidates: &RoaringBitmap,
+    matched: &mut RoaringBitmap,
+) -> Vec<(MetadataValue, RoaringBitmap)> {
+    buckets
+        .into_iter()
+        .filter_map(|(value, bitmap)| {
+            let restricted = &bitmap & candidates;
+            if restricted.is_empty() {
+                None
+            } else {
+                *matched |= &restricted;
+                Some((value, restricted))
+            }
+        })
+        .collect()
+}
+
+// Merges two already-ordered bucket sequences into one, unioning bitmaps for equal values.
+fn merge_buckets(
+    left: Vec<(MetadataValue, RoaringBitmap)>,
+    right: Vec<(MetadataValue, RoaringBitmap)>,
+    ascending: bool,
+) -> Vec<(MetadataValue, RoaringBitmap)> {
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    let mut merged = Vec::new();
+    loop {
+        let take_left = match (left.peek(), right.peek()) {
+            (Some((lv, _)), Some((rv, _))) => {
+                if ascending {
+                    lv <= rv
+                } else {
+                    lv >= rv
+                }
+            }
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => break,
+        };
+        let (value, bitmap) = if take_left {
+            left.next().unwrap()
+        } else {
+            right.next().unwrap()
+        };
+        match merged.last_mut() {
+            Some((last_value, last_bitmap)) if *last_value == value => {
+                *last_bitmap |= bitmap;
+            }
+            _ => merged.push((value, bitmap)),
+        }
+    }
+    merged
+}
+
+#[async_trait]
+impl Operator<SortInput, SortOutput> for SortOperator {
+    type Error = SortError;
+
+    async fn run(&self, input: &SortInput) -> Result<SortOutput, SortError> {
+        trace!("[{}]: {:?}", self.get_name(), input);
+
+        let record_segment_reader = input.segments.record_segment_reader().await?;
+        let materializer =
+            LogMaterializer::new(record_segment_reader.clone(), input.logs.clone(), None);
+        let materialized_logs = materializer
+            .materialize()
+            .instrument(tracing::trace_span!(parent: Span::current(), "Materialize logs"))
+            .await?;
+        let metadata_log_reader = MetadataLogReader::new(&materialized_logs);
+        let log_metadata_provider =
+            MetadataProvider::from_metadata_log_reader(&metadata_log_reader);
+
+        let metadata_segment_reader = input.segments.metadata_segment_reader().await?;
+        let compact_metadata_provider =
+            MetadataProvider::from_metadata_segment_reader(&metadata_segment_reader);
+
+        let log_candidates = metadata_log_reader.materialize(&input.log_oids);
+        let log_ordered = log_metadata_provider
+            .ordered_values(&self.key, self.ascending)
+            .await?;
+        let mut matched = RoaringBitmap::new();
+        let log_ordered = restrict_to_candidates(log_ordered, &log_candidates, &mut matched);
+        let log_missing = &log_candidates - &matched;
+
+        // The union of the sort key's own buckets only covers oids that *have* the key, so using
+        // it as the universe for an `Exclude` candidate set would silently drop every compacted
+        // record missing the key instead of grouping them at the end. Resolve against the record
+        // segment's true oid universe instead.
+        let compact_universe = match record_segment_reader.as_ref() {
+            Some(reader) => reader.get_all_offset_ids().await,
+            None => RoaringBitmap::new(),
+        };
+        let compact_ordered = compact_metadata_provider
+            .ordered_values(&self.key, self.ascending)
+            .await?;
+        let compact_candidates =
+            compact_metadata_provider.materialize(&input.compact_oids, &compact_universe);
+        let mut compact_matched = RoaringBitmap::new();
+        let compact_ordered =
+            restrict_to_candidates(compact_ordered, &compact_candidates, &mut compact_matched);
+        let compact_missing = &compact_candidates - &compact_matched;
+
+        let merged = merge_buckets(log_ordered, compact_ordered, self.ascending);
+        let missing = log_missing | compact_missing;
+
+        let mut offset_ids: Vec<u32> = merged
+            .into_iter()
+            .flat_map(|(_, bitmap)| bitmap.into_iter())
+            .chain(missing.into_iter())
+            .collect();
+
+        if let Some(limit) = self.limit {
+            offset_ids.truncate(limit as usize);
+        }
+
+        Ok(SortOutput { offset_ids })
+    }
+}